@@ -1,15 +1,34 @@
 use crate::config::Config;
+use crate::lint::lint_commit_message;
+use crate::notify;
+use crate::stream;
 use anyhow::{ensure, Context, Result};
 use async_openai::types::{
     ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
     ChatCompletionResponseMessage, CreateChatCompletionRequestArgs,
 };
-use inquire::{required, Text};
+use futures::future::join_all;
+use git2::{DiffFormat, Repository};
+use inquire::validator::{ErrorMessage, Validation};
+use inquire::{required, Editor, Select, Text};
 use regex::Regex;
 use reqwest::Client;
 use serde::Deserialize;
-use tokio::process::Command;
-use which::which;
+
+/// Distinct system-prompt styles used to diversify candidates when
+/// `Config::use_candidate_styles` is enabled. Each style is appended to `config.commit_prompt`
+/// to bias the model towards a different kind of commit message.
+const CANDIDATE_STYLES: &[(&str, &str)] = &[
+    ("terse", "Keep the message as short as possible, ideally a single clause."),
+    (
+        "conventional",
+        "Strictly follow the Conventional Commits format, e.g. `feat(scope): description`.",
+    ),
+    (
+        "descriptive",
+        "Favor a fuller description of what changed and why over brevity.",
+    ),
+];
 
 /// Stores a single commit message candidate generated by the model
 #[derive(Deserialize)]
@@ -23,86 +42,219 @@ struct CommitMessageCandidates {
     choices: Vec<CommitMessageCandidate>,
 }
 
-/// Asynchronously executes a Git command with the specified arguments.
-///
-/// Invokes a Git command with the given arguments, waits for the command to complete,
-/// and returns the command's standard output as a string if successful.
+/// A commit message split into its subject line and an optional body.
 ///
-/// # Arguments
+/// `body` is `None` for single-line messages (the default) and `Some` when `Config::allow_body`
+/// lets the model produce a wrapped body, separated from the subject by a blank line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessage {
+    pub subject: String,
+    pub body: Option<String>,
+}
+
+impl std::fmt::Display for CommitMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.body {
+            Some(body) => write!(f, "{}\n\n{}", self.subject, body),
+            None => write!(f, "{}", self.subject),
+        }
+    }
+}
+
+impl CommitMessage {
+    /// Splits `message` into a subject (the first line) and an optional body (every remaining
+    /// line joined back together and trimmed, `None` if empty).
+    pub fn from_str_lossy(message: &str) -> Self {
+        let mut lines = message.lines();
+        let subject = lines.next().unwrap_or_default().trim().to_string();
+
+        let body = lines.collect::<Vec<_>>().join("\n");
+        let body = body.trim();
+
+        CommitMessage {
+            subject,
+            body: if body.is_empty() {
+                None
+            } else {
+                Some(body.to_string())
+            },
+        }
+    }
+}
+
+/// Opens the Git repository the current directory belongs to.
 ///
-/// * `args` - A slice of string references representing the arguments for the Git command.
+/// Walks up from the current directory looking for a `.git` directory, mirroring what `git`
+/// itself does, so the tool keeps working from any subdirectory of the repository.
 ///
 /// # Returns
 ///
-/// Returns a `Result` containing the standard output of the Git command as a string on success,
-/// or an error if there were issues executing the command or decoding the output.
-async fn run_git_command(args: &[&str]) -> Result<String> {
-    let response = Command::new("git")
-        .args(args)
-        .output()
-        .await
-        .context("Failed to execute Git command.")?;
-
-    ensure!(
-        response.status.success(),
-        "{}",
-        String::from_utf8_lossy(&response.stderr)
-    );
-
-    String::from_utf8(response.stdout).context("Failed to decode output of the Git command.")
+/// Returns a `Result` containing the discovered `Repository` on success, or an error if the
+/// current directory is not inside a Git repository.
+fn open_repository() -> Result<Repository> {
+    Repository::discover(".").context("The current directory is not a Git repository.")
 }
 
-/// Asynchronously performs Git-related checks to ensure Git is installed and the current directory is a Git repository.
-///
-/// Checks whether Git is installed and if the current directory is a Git repository by invoking
-/// relevant Git commands. Returns a `Result` indicating success or an error if Git is not installed
-/// or the current directory is not a Git repository.
+/// Performs Git-related checks to ensure the current directory is a Git repository.
 ///
 /// # Returns
 ///
-/// Returns a `Result` indicating success on passing Git checks or an error with a relevant message if checks fail.
+/// Returns a `Result` indicating success on passing Git checks or an error with a relevant
+/// message if checks fail.
 pub async fn git_checks() -> Result<()> {
-    which("git").context("Git may not be installed.")?;
-
-    run_git_command(&["rev-parse", "--is-inside-work-tree"])
-        .await
-        .context("The current directory is not a Git respository.")?;
+    open_repository()?;
 
     Ok(())
 }
 
-pub async fn git_commit(commit_message: &str) -> Result<String> {
-    let result = run_git_command(&["commit", "-m", commit_message])
-        .await?
-        .trim()
-        .to_string();
+/// Computes the diffstat between `parent_commit` (if any) and `tree`, for commit notifications.
+fn compute_commit_stats(
+    repo: &Repository,
+    parent_commit: Option<&git2::Commit>,
+    tree: &git2::Tree,
+) -> Result<notify::CommitStats> {
+    let parent_tree = parent_commit
+        .map(git2::Commit::tree)
+        .transpose()
+        .context("Failed to resolve the parent commit's tree.")?;
 
-    Ok(result)
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(tree), None)
+        .context("Failed to diff the new commit against its parent.")?;
+    let stats = diff
+        .stats()
+        .context("Failed to compute the diffstat for the new commit.")?;
+
+    Ok(notify::CommitStats {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
 }
 
-/// Asynchronously retrieves the staged Git differences.
+/// Creates a commit from the current index, using the repository's configured author/committer
+/// identity and the current `HEAD` commit (if any) as the sole parent. When
+/// `Config::notify_webhook_url` and/or `Config::notify_email` are set, sends a notification about
+/// the new commit and waits for it to finish; a failed notification is logged to stderr and never
+/// turns the commit into an error.
+pub async fn git_commit(
+    http_client: &Client,
+    config: &Config,
+    commit_message: &CommitMessage,
+) -> Result<String> {
+    let repo = open_repository()?;
+
+    let mut index = repo.index().context("Failed to read the Git index.")?;
+    let tree_oid = index
+        .write_tree()
+        .context("Failed to write a Git tree from the index.")?;
+    let tree = repo
+        .find_tree(tree_oid)
+        .context("Failed to look up the written Git tree.")?;
+
+    let signature = repo
+        .signature()
+        .context("Failed to resolve the Git author/committer identity.")?;
+
+    let parent_commit = match repo.head() {
+        Ok(head) => Some(
+            head.peel_to_commit()
+                .context("Failed to resolve the current HEAD commit.")?,
+        ),
+        Err(_) => None, // No HEAD yet, e.g. the first commit in a new repository
+    };
+    let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
+
+    let commit_oid = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &commit_message.to_string(),
+            &tree,
+            &parents,
+        )
+        .context("Failed to create the Git commit.")?;
+
+    if config.notify_webhook_url.is_some() || config.notify_email.is_some() {
+        // The commit above already succeeded — a notification going missing (including failing
+        // to compute its diffstat) must never turn that success into an error for the caller.
+        match compute_commit_stats(&repo, parent_commit.as_ref(), &tree) {
+            Ok(stats) => {
+                let repository_name = repo
+                    .workdir()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "repository".to_string());
+
+                // Awaited here (rather than left fire-and-forget) so a short-lived CLI's runtime
+                // doesn't shut down and drop the notification before it sends.
+                let notify_handle = notify::notify_commit(
+                    http_client.clone(),
+                    config.clone(),
+                    repository_name,
+                    commit_oid.to_string(),
+                    commit_message.clone(),
+                    signature.name().unwrap_or("unknown").to_string(),
+                    signature.email().unwrap_or("unknown").to_string(),
+                    stats,
+                );
+                notify_handle.await.ok();
+            }
+            Err(error) => {
+                eprintln!("Failed to compute commit stats for notification: {error:#}");
+            }
+        }
+    }
+
+    Ok(commit_oid.to_string())
+}
+
+/// Retrieves the staged Git differences, excluding lockfiles.
 ///
-/// Executes the Git command to retrieve the staged differences, ensuring that there are staged changes
-/// to commit. Returns the staged differences as a string if successful.
+/// Diffs the repository's `HEAD` tree against the index, ensuring that there are staged changes
+/// to commit. Returns the staged differences as a unified patch string if successful.
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing the staged Git differences as a string on success,
-/// or an error if there were issues executing the Git command or if there are no staged changes.
+/// or an error if there were issues computing the diff or if there are no staged changes.
 pub async fn git_diff() -> Result<String> {
-    let git_diffs = run_git_command(&[
-        "--no-pager",
-        "diff",
-        "--staged",
-        "--minimal",
-        "--no-color",
-        "--no-ext-diff",
-        "--",
-        ":!*.lock",
-    ])
-    .await?
-    .trim()
-    .to_string();
+    let repo = open_repository()?;
+
+    let head_tree = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .ok();
+    let index = repo.index().context("Failed to read the Git index.")?;
+
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(&index), None)
+        .context("Failed to compute the staged Git diff.")?;
+
+    let mut git_diffs = String::new();
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        // Lockfiles (`*.lock`) are left out of the rendered diff.
+        let is_lockfile = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .and_then(|path| path.extension())
+            .is_some_and(|extension| extension == "lock");
+        if is_lockfile {
+            return true;
+        }
+
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            git_diffs.push(line.origin());
+        }
+        git_diffs.push_str(&String::from_utf8_lossy(line.content()));
+
+        true
+    })
+    .context("Failed to render the staged Git diff.")?;
+
+    let git_diffs = git_diffs.trim().to_string();
 
     ensure!(
         !git_diffs.is_empty(),
@@ -112,33 +264,101 @@ pub async fn git_diff() -> Result<String> {
     Ok(git_diffs)
 }
 
-/// Asynchronously generates a commit message using the provided HTTP client, configuration, and Git differences.
-///
-/// Constructs a request payload for the API based on the provided configuration and staged Git differences.
-/// Sends the request to the API provider, retrieves and parses the response, and extracts the generated
-/// commit message. Post-processes the commit message to keep only the first line and remove leading and trailing backticks.
-///
-/// # Arguments
-///
-/// * `http_client` - A reference to the Reqwest HTTP client used to send requests to the API.
-/// * `config` - A reference to the configuration containing API details, model information, and prompt contents.
-/// * `diff` - A reference to the staged Git differences to be included in the user prompt.
+/// Post-processes a raw model response by stripping a surrounding code fence or backticks, then
+/// splitting the result into a subject and an optional body.
 ///
-/// # Returns
+/// When `allow_body` is `false` the body (if any) is discarded, keeping the single-line-message
+/// behavior callers relied on before `Config::allow_body` existed.
+fn postprocess_commit_message(raw_commit_message: &str, allow_body: bool) -> Result<CommitMessage> {
+    let trimmed = raw_commit_message.trim();
+
+    let fenced_regex = Regex::new(r"(?s)^```[a-zA-Z]*\s*\n?(.*?)\n?```$")?;
+    let backtick_regex = Regex::new(r"^`\s*(.*?)\s*`$")?;
+
+    let unfenced = if let Some(captures) = fenced_regex.captures(trimmed) {
+        captures.get(1).unwrap().as_str()
+    } else if let Some(captures) = backtick_regex.captures(trimmed) {
+        captures.get(1).unwrap().as_str()
+    } else {
+        trimmed
+    };
+
+    let mut commit_message = CommitMessage::from_str_lossy(unfenced.trim());
+    if !allow_body {
+        commit_message.body = None;
+    }
+
+    ensure!(
+        !commit_message.subject.is_empty(),
+        "Failed to post-process the generated commit message"
+    );
+
+    Ok(commit_message)
+}
+
+/// Sends a single commit-message generation request using `system_prompt`, returning every
+/// choice the API provider returned, post-processed and ready for display.
 ///
-/// Returns a `Result` containing the generated commit message as a string on success,
-/// or an error if there were issues constructing the request, sending it, or processing the response.
-pub async fn generate_commit_message(
+/// `echo` is only honored in the streaming case, where it controls whether the first candidate's
+/// tokens are printed to stderr as they arrive — pass `false` when multiple calls are in flight
+/// concurrently to avoid interleaving their output.
+async fn request_commit_message_candidates(
     http_client: &Client,
     config: &Config,
+    system_prompt: &str,
     diff: &str,
-) -> Result<String> {
+    num_candidates: u8,
+    echo: bool,
+) -> Result<Vec<CommitMessage>> {
+    let system_prompt = if config.allow_body {
+        format!("{system_prompt}\n\n{}", config.commit_body_prompt)
+    } else {
+        system_prompt.to_string()
+    };
+
+    if config.stream_responses {
+        let payload = CreateChatCompletionRequestArgs::default()
+            .max_tokens(config.max_chars)
+            .model(&config.git_model_name)
+            .n(num_candidates)
+            .stream(true)
+            .messages([
+                ChatCompletionRequestSystemMessageArgs::default()
+                    .content(system_prompt)
+                    .build()?
+                    .into(),
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(config.diff_prompt.replace("{}", diff))
+                    .build()?
+                    .into(),
+            ])
+            .build()
+            .context("Failed to construct the request payload")?;
+
+        let response = http_client
+            .post(format!("{}/chat/completions", &config.git_api_base_url))
+            .bearer_auth(&config.api_key)
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send the request to the API provider")?
+            .error_for_status()?;
+
+        let raw_commit_messages = stream::consume_chat_completion_stream(response, echo).await?;
+
+        return raw_commit_messages
+            .iter()
+            .map(|raw_commit_message| postprocess_commit_message(raw_commit_message, config.allow_body))
+            .collect();
+    }
+
     let payload = CreateChatCompletionRequestArgs::default()
         .max_tokens(config.max_chars)
         .model(&config.git_model_name)
+        .n(num_candidates)
         .messages([
             ChatCompletionRequestSystemMessageArgs::default()
-                .content(&config.commit_prompt)
+                .content(system_prompt)
                 .build()?
                 .into(),
             ChatCompletionRequestUserMessageArgs::default()
@@ -161,41 +381,164 @@ pub async fn generate_commit_message(
         .await
         .context("Failed to parse the response from the API provider")?;
 
-    let commit_message = response
+    response
         .choices
-        .first() // Only the first generated commit message is used
-        .context("No commit messages generated")?
-        .message
-        .content
-        .as_ref()
-        .context("No commit messages generated")?;
-
-    // Post-process the generated commit message to keep only the first line and remove leading and trailing backticks
-    let regex_matches = Regex::new(r"(?m)^\s*(?:`\s*(.+?)\s*`|(.+?))\s*$")?
-        .captures(commit_message)
-        .context("Failed to post-process the generated commit message")?;
-
-    let commit_message = regex_matches
-        .get(1)
-        .or(regex_matches.get(2))
-        .context("Failed to post-process the generated commit message")?
-        .as_str()
-        .to_string();
+        .iter()
+        .map(|choice| {
+            let raw_commit_message = choice
+                .message
+                .content
+                .as_ref()
+                .context("No commit messages generated")?;
 
-    Ok(commit_message)
+            postprocess_commit_message(raw_commit_message, config.allow_body)
+        })
+        .collect()
 }
 
-pub fn edit_commit_message(generated_commit_message: &str) -> Result<String> {
-    // Ask user to edit the generated commit message if needed
-    let edited_commit_message = Text::new("Your generated commit message:")
-        .with_initial_value(&generated_commit_message)
-        .with_validator(required!(
-            "Please provide a commit message to create a commit"
-        ))
-        .with_help_message(
-            "Press Enter to create a new commit with the current message or ESC to cancel",
-        )
-        .prompt()?;
+/// Asynchronously generates one or more commit message candidates using the provided HTTP
+/// client, configuration, and Git differences.
+///
+/// Constructs a request payload for the API based on the provided configuration and staged Git
+/// differences, requesting `config.num_candidates` choices. Every returned choice is
+/// post-processed to remove a surrounding code fence or backticks and split into a subject and
+/// body; the body is dropped unless `config.allow_body` is set.
+///
+/// When `config.use_candidate_styles` is enabled, `config.num_candidates` is ignored in favor of
+/// sending one parallel request per entry in `CANDIDATE_STYLES`, each biased towards a different
+/// kind of commit message via the system prompt.
+///
+/// # Arguments
+///
+/// * `http_client` - A reference to the Reqwest HTTP client used to send requests to the API.
+/// * `config` - A reference to the configuration containing API details, model information, and prompt contents.
+/// * `diff` - A reference to the staged Git differences to be included in the user prompt.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the generated commit message candidates on success,
+/// or an error if there were issues constructing the request, sending it, or processing the response.
+pub async fn generate_commit_message(
+    http_client: &Client,
+    config: &Config,
+    diff: &str,
+) -> Result<Vec<CommitMessage>> {
+    if config.use_candidate_styles {
+        // Style requests run concurrently via `join_all`, so echoing every one of them would
+        // interleave their tokens into unreadable stderr noise — only live-echo when a single
+        // request is in flight, which is never the case here.
+        let requests = CANDIDATE_STYLES.iter().map(|(_, style_instruction)| {
+            let system_prompt = format!("{}\n\n{}", config.commit_prompt, style_instruction);
+            request_commit_message_candidates(http_client, config, &system_prompt, diff, 1, false)
+        });
+
+        let candidates: Vec<CommitMessage> = join_all(requests)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<Vec<CommitMessage>>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        return Ok(candidates);
+    }
+
+    request_commit_message_candidates(
+        http_client,
+        config,
+        &config.commit_prompt,
+        diff,
+        config.num_candidates,
+        true,
+    )
+    .await
+}
+
+/// Presents `candidates` to the user through an `inquire::Select` list so they can pick the best
+/// one. Falls back to returning the only candidate directly when there is just one.
+///
+/// `use_candidate_styles` should be `config.use_candidate_styles` from the call that produced
+/// `candidates` — it's only safe to label entries with `CANDIDATE_STYLES` names when the
+/// candidates were actually generated one-per-style; otherwise plain candidates get labeled with
+/// a generic index.
+pub fn select_commit_message(
+    candidates: Vec<CommitMessage>,
+    use_candidate_styles: bool,
+) -> Result<CommitMessage> {
+    if candidates.len() == 1 {
+        return Ok(candidates.into_iter().next().unwrap());
+    }
+
+    let labels: Vec<String> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let style = if use_candidate_styles {
+                CANDIDATE_STYLES
+                    .get(index)
+                    .map(|(name, _)| format!("{name}: "))
+                    .unwrap_or_default()
+            } else {
+                format!("{}: ", index + 1)
+            };
+            let body_marker = if candidate.body.is_some() { " (+body)" } else { "" };
+            format!("{style}{}{body_marker}", candidate.subject)
+        })
+        .collect();
+
+    let selected_label = Select::new("Choose a commit message:", labels.clone())
+        .prompt()
+        .context("Failed to select a commit message")?;
+
+    let selected_index = labels
+        .iter()
+        .position(|label| label == &selected_label)
+        .context("Selected commit message not found among candidates")?;
+
+    Ok(candidates[selected_index].clone())
+}
+
+/// Builds the shared lint validator used by both the single-line and multi-line edit prompts.
+fn lint_validator(config: Config) -> impl Fn(&str) -> Result<Validation, inquire::CustomUserError> {
+    move |message: &str| {
+        let violations = lint_commit_message(message, &config);
+        if violations.is_empty() {
+            Ok(Validation::Valid)
+        } else {
+            let report = violations
+                .iter()
+                .map(|violation| violation.to_string())
+                .collect::<Vec<_>>()
+                .join("; ");
+            Ok(Validation::Invalid(ErrorMessage::Custom(report)))
+        }
+    }
+}
+
+pub fn edit_commit_message(generated_commit_message: &str, config: &Config) -> Result<String> {
+    // `Text` can only ever hold a single line, so a message with a body (`config.allow_body`)
+    // needs `Editor` instead — otherwise rules like `lint_require_blank_line_before_body` could
+    // never fire from this prompt.
+    let edited_commit_message = if config.allow_body {
+        Editor::new("Your generated commit message:")
+            .with_predefined_text(generated_commit_message)
+            .with_validator(lint_validator(config.clone()))
+            .with_help_message(
+                "An editor will open so you can add a body; save and close to create the commit",
+            )
+            .prompt()?
+    } else {
+        Text::new("Your generated commit message:")
+            .with_initial_value(generated_commit_message)
+            .with_validator(required!(
+                "Please provide a commit message to create a commit"
+            ))
+            .with_validator(lint_validator(config.clone()))
+            .with_help_message(
+                "Press Enter to create a new commit with the current message or ESC to cancel",
+            )
+            .prompt()?
+    };
 
     Ok(edited_commit_message)
 }
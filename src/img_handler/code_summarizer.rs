@@ -1,17 +1,15 @@
-use std::slice::SliceIndex;
-
 use anyhow::{Context, Result};
 use async_openai::types::{
     ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-    ChatCompletionRequestUserMessageArgs, ChatCompletionResponseMessage,
-    CreateChatCompletionRequestArgs,
+    ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs,
 };
+use futures::future::try_join_all;
 
-use regex::Regex;
 use reqwest::Client;
 use serde::Deserialize;
 
 use crate::app_config::config::Config;
+use crate::stream;
 
 /// Stores a single summary message candidate generated by the model
 #[derive(Deserialize)]
@@ -29,59 +27,141 @@ struct MessageContent {
     content: String,
 }
 
-pub async fn generate_slide_summary(
+/// Splits `file_text_list` into batches whose combined length (in bytes) is at most
+/// `budget_chars`, so a single "map" request doesn't overflow the model's context window. A
+/// single file longer than `budget_chars` is not split; it forms a batch of its own.
+fn chunk_file_texts(file_text_list: Vec<String>, budget_chars: usize) -> Vec<Vec<String>> {
+    let mut batches: Vec<Vec<String>> = Vec::new();
+    let mut current_batch: Vec<String> = Vec::new();
+    let mut current_chars = 0;
+
+    for file_text in file_text_list {
+        if !current_batch.is_empty() && current_chars + file_text.len() > budget_chars {
+            batches.push(std::mem::take(&mut current_batch));
+            current_chars = 0;
+        }
+
+        current_chars += file_text.len();
+        current_batch.push(file_text);
+    }
+
+    if !current_batch.is_empty() {
+        batches.push(current_batch);
+    }
+
+    batches
+}
+
+/// Sends a single chat-completion request with `system_prompt` as the system message and one
+/// user message per entry of `file_text_list`, returning the model's response content.
+///
+/// `echo` controls whether streamed tokens are printed to stderr as they arrive.
+async fn request_summary(
     http_client: &Client,
     config: &Config,
-    file_text_list: Vec<String>,
+    system_prompt: &str,
+    file_text_list: &[String],
+    echo: bool,
 ) -> Result<String> {
-    
-    let mut chat_completion_request_system_message_args_list: Vec<ChatCompletionRequestMessage> = Vec::new();
-
-    chat_completion_request_system_message_args_list.push(
-        ChatCompletionRequestSystemMessageArgs::default()
-            .content(&config.slides_prompt)
-            .build()?
-            .into(),
-    );
+    let mut messages: Vec<ChatCompletionRequestMessage> = vec![ChatCompletionRequestSystemMessageArgs::default()
+        .content(system_prompt)
+        .build()?
+        .into()];
 
     for file_text in file_text_list {
-
-        chat_completion_request_system_message_args_list.push(
+        messages.push(
             ChatCompletionRequestUserMessageArgs::default()
-                .content(file_text)
+                .content(file_text.clone())
                 .build()?
                 .into(),
-        )
+        );
+    }
+
+    if config.stream_responses {
+        let payload = CreateChatCompletionRequestArgs::default()
+            .max_tokens(config.max_chars)
+            .model(&config.git_model_name)
+            .stream(true)
+            .messages(messages)
+            .build()
+            .context("Failed to construct the request payload")?;
+
+        let response = http_client
+            .post(&config.git_api_base_url)
+            .bearer_auth(&config.api_key)
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send the request to the API provider")?
+            .error_for_status()
+            .context("The API provider returned an error status")?;
+
+        let summary_message = stream::consume_chat_completion_stream(response, echo)
+            .await?
+            .into_iter()
+            .next()
+            .context("No summary was generated")?;
+
+        return Ok(summary_message);
     }
 
     let payload = CreateChatCompletionRequestArgs::default()
         .max_tokens(config.max_chars)
         .model(&config.git_model_name)
-        .messages(chat_completion_request_system_message_args_list)
+        .messages(messages)
         .build()
         .context("Failed to construct the request payload")?;
 
     let response = http_client
-        .post(format!("{}", &config.git_api_base_url))
+        .post(&config.git_api_base_url)
         .bearer_auth(&config.api_key)
         .json(&payload)
         .send()
         .await
         .context("Failed to send the request to the API provider")?;
-        
+
     let summary_message = response
         .json::<SlideResponse>()
-        .await?
+        .await
+        .context("Failed to parse the response from the API provider")?
         .choices
-        .first() // Only the first generated summary message is used
-        .unwrap() // Unwrap the Option<&code_summarizer::Choice> to access the Choice struct
+        .into_iter()
+        .next()
+        .context("No summary was generated")?
         .message
-        .content
-        .clone();
+        .content;
+
+    Ok(summary_message)
+}
+
+/// Summarizes `file_text_list` using a map-reduce pass so large decks don't overflow the
+/// model's context window.
+///
+/// `file_text_list` is first chunked into batches that each fit `config.slides_chunk_char_budget`
+/// characters. Every batch is summarized independently using `config.slides_map_prompt` (the
+/// "map" requests, run concurrently), then the concatenated partial summaries are fed back into
+/// a final "reduce" request using `config.slides_prompt` to produce the unified summary.
+pub async fn generate_slide_summary(
+    http_client: &Client,
+    config: &Config,
+    file_text_list: Vec<String>,
+) -> Result<String> {
+    let batches = chunk_file_texts(file_text_list, config.slides_chunk_char_budget);
+
+    // Map requests run concurrently, so only the single reduce request echoes its tokens.
+    let map_requests = batches.iter().map(|batch| {
+        request_summary(http_client, config, &config.slides_map_prompt, batch, false)
+    });
+
+    let partial_summaries = try_join_all(map_requests).await?;
 
-    println!("{}", summary_message);
+    // Always run the reduce request, even for a single batch, so the returned summary is driven
+    // by `config.slides_prompt` rather than the map step's raw `slides_map_prompt` output.
+    let reduce_input = vec![partial_summaries.join("\n\n")];
+    let summary_message =
+        request_summary(http_client, config, &config.slides_prompt, &reduce_input, true).await?;
 
-    
+    println!("{summary_message}");
 
     Ok(summary_message)
 }
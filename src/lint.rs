@@ -0,0 +1,228 @@
+use crate::config::Config;
+use anyhow::{ensure, Context, Result};
+use regex::Regex;
+
+/// The Conventional Commits types accepted when `Config::lint_allowed_types` is left empty.
+const DEFAULT_ALLOWED_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Common non-imperative leading words used as the heuristic behind
+/// `Config::lint_enforce_imperative_mood`. Catches the most common "added X" / "fixes Y" style
+/// descriptions; it is not a full grammar check.
+const NON_IMPERATIVE_LEADING_WORDS: &[&str] = &[
+    "added", "adds", "adding", "fixed", "fixes", "fixing", "updated", "updates", "updating",
+    "removed", "removes", "removing", "changed", "changes", "changing", "refactored",
+    "refactors", "refactoring", "implemented", "implements", "implementing",
+];
+
+/// A commit header/body broken down into its Conventional Commits components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCommit {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+    pub body: Option<String>,
+    pub footers: Vec<String>,
+}
+
+/// A single rule violation found while linting a commit message.
+///
+/// `line` and `column` are 1-indexed so they can be surfaced directly to the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintViolation {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Splits a Conventional Commits header into type, scope, breaking marker, and description.
+///
+/// Returns `None` if `header` does not match `<type>[optional scope][!]: <description>`.
+pub fn parse_header(header: &str) -> Option<(String, Option<String>, bool, String)> {
+    let header_regex = Regex::new(r"^(?P<type>[a-zA-Z]+)(?:\((?P<scope>[^)]+)\))?(?P<breaking>!)?: (?P<description>.+)$").ok()?;
+    let captures = header_regex.captures(header)?;
+
+    let commit_type = captures.name("type")?.as_str().to_string();
+    let scope = captures.name("scope").map(|m| m.as_str().to_string());
+    let breaking = captures.name("breaking").is_some();
+    let description = captures.name("description")?.as_str().to_string();
+
+    Some((commit_type, scope, breaking, description))
+}
+
+/// Parses a full commit message (header, optional body, optional footers) into a `ParsedCommit`.
+///
+/// The body and footers are separated from the header by a blank line, per Conventional Commits.
+pub fn parse_commit(message: &str) -> Option<ParsedCommit> {
+    let mut lines = message.lines();
+    let header = lines.next()?;
+    let (commit_type, scope, breaking, description) = parse_header(header)?;
+
+    let rest: Vec<&str> = lines.collect();
+    let body_and_footers = rest.join("\n");
+    let body_and_footers = body_and_footers.trim_start_matches('\n');
+
+    let (body, footers) = if body_and_footers.trim().is_empty() {
+        (None, Vec::new())
+    } else {
+        let footer_regex = Regex::new(r"(?m)^[A-Za-z-]+: .+$|^BREAKING CHANGE: .+$").ok()?;
+        let footers: Vec<String> = footer_regex
+            .find_iter(body_and_footers)
+            .map(|m| m.as_str().to_string())
+            .collect();
+        let body = body_and_footers.trim().to_string();
+        (if body.is_empty() { None } else { Some(body) }, footers)
+    };
+
+    Some(ParsedCommit {
+        commit_type,
+        scope,
+        breaking,
+        description,
+        body,
+        footers,
+    })
+}
+
+/// Lints `message` against `config`'s Conventional Commits rules, returning every violation found.
+///
+/// An empty `Vec` means the message is valid. Each violation carries the line/column it applies to
+/// so it can be surfaced next to the offending text rather than as a single opaque error.
+pub fn lint_commit_message(message: &str, config: &Config) -> Vec<LintViolation> {
+    let mut violations = Vec::new();
+
+    let header = match message.lines().next() {
+        Some(header) if !header.is_empty() => header,
+        _ => {
+            violations.push(LintViolation {
+                line: 1,
+                column: 1,
+                message: "Commit message must not be empty.".to_string(),
+            });
+            return violations;
+        }
+    };
+
+    let Some((commit_type, _scope, _breaking, description)) = parse_header(header) else {
+        violations.push(LintViolation {
+            line: 1,
+            column: 1,
+            message: "Header does not match `<type>[optional scope][!]: <description>`."
+                .to_string(),
+        });
+        return violations;
+    };
+
+    if config.lint_enforce_allowed_types {
+        let allowed_types: Vec<&str> = if config.lint_allowed_types.is_empty() {
+            DEFAULT_ALLOWED_TYPES.to_vec()
+        } else {
+            config.lint_allowed_types.iter().map(String::as_str).collect()
+        };
+
+        if !allowed_types.contains(&commit_type.as_str()) {
+            violations.push(LintViolation {
+                line: 1,
+                column: 1,
+                message: format!(
+                    "Unknown commit type `{commit_type}`, expected one of: {}.",
+                    allowed_types.join(", ")
+                ),
+            });
+        }
+    }
+
+    let header_char_count = header.chars().count();
+    if header_char_count > config.lint_subject_max_length {
+        violations.push(LintViolation {
+            line: 1,
+            column: config.lint_subject_max_length + 1,
+            message: format!(
+                "Subject line is {header_char_count} characters, exceeds the max of {}.",
+                config.lint_subject_max_length
+            ),
+        });
+    }
+
+    if config.lint_require_lowercase_description {
+        if let Some(first_char) = description.chars().next() {
+            if first_char.is_uppercase() {
+                violations.push(LintViolation {
+                    line: 1,
+                    column: header.len() - description.len() + 1,
+                    message: "Description must start with a lowercase letter.".to_string(),
+                });
+            }
+        }
+    }
+
+    if config.lint_enforce_imperative_mood {
+        if let Some(first_word) = description.split_whitespace().next() {
+            let first_word_lower = first_word.to_lowercase();
+            let looks_non_imperative = NON_IMPERATIVE_LEADING_WORDS
+                .contains(&first_word_lower.as_str())
+                || (first_word_lower.len() > 3 && first_word_lower.ends_with("ed"))
+                || (first_word_lower.len() > 4 && first_word_lower.ends_with("ing"));
+
+            if looks_non_imperative {
+                violations.push(LintViolation {
+                    line: 1,
+                    column: header.len() - description.len() + 1,
+                    message: format!(
+                        "Description should use the imperative mood (e.g. \"add\" not \"{first_word}\")."
+                    ),
+                });
+            }
+        }
+    }
+
+    if config.lint_forbid_trailing_period && description.ends_with('.') {
+        violations.push(LintViolation {
+            line: 1,
+            column: header.len(),
+            message: "Description must not end with a trailing period.".to_string(),
+        });
+    }
+
+    if config.lint_require_blank_line_before_body {
+        let mut lines = message.lines();
+        lines.next();
+        if let Some(second_line) = lines.next() {
+            if !second_line.is_empty() {
+                violations.push(LintViolation {
+                    line: 2,
+                    column: 1,
+                    message: "Expected a blank line between the subject and the body."
+                        .to_string(),
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Validates `message` against `config`, returning the `ParsedCommit` on success or an error
+/// listing every violation found.
+pub fn validate_commit_message(message: &str, config: &Config) -> Result<ParsedCommit> {
+    let violations = lint_commit_message(message, config);
+    ensure!(
+        violations.is_empty(),
+        "{}",
+        violations
+            .iter()
+            .map(LintViolation::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+
+    parse_commit(message).context("Failed to parse a commit message that passed linting")
+}
@@ -0,0 +1,173 @@
+use crate::config::Config;
+use crate::git::CommitMessage;
+use anyhow::{Context, Result};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use reqwest::Client;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+/// Diffstat summary attached to a commit notification.
+#[derive(Debug, Clone, Copy)]
+pub struct CommitStats {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Serialize)]
+struct WebhookAuthor {
+    name: String,
+    email: String,
+}
+
+/// A commit entry in the webhook payload. Loosely modeled on a push-event commit (repository,
+/// sha, message, author) but `insertions`/`deletions` are diffstat counts, not GitHub's
+/// `added`/`removed` file-path arrays — this payload is not a drop-in push-event replacement.
+#[derive(Serialize)]
+struct WebhookCommit {
+    id: String,
+    message: String,
+    author: WebhookAuthor,
+    insertions: usize,
+    deletions: usize,
+}
+
+#[derive(Serialize)]
+struct WebhookPayload {
+    repository: String,
+    commits: Vec<WebhookCommit>,
+}
+
+/// Spawns a task that notifies whichever destinations are configured
+/// (`Config::notify_webhook_url`, `Config::notify_email`, or both) about a newly created commit.
+/// Failures are logged to stderr and never propagate, since a notification going missing should
+/// not retroactively fail a commit that already succeeded.
+///
+/// Returns the task's `JoinHandle` — the caller must `.await` it (e.g. right before the process
+/// exits) since a short-lived CLI's runtime shutting down would otherwise drop the notification
+/// mid-flight and it would simply never send.
+pub fn notify_commit(
+    http_client: Client,
+    config: Config,
+    repository_name: String,
+    commit_sha: String,
+    commit_message: CommitMessage,
+    author_name: String,
+    author_email: String,
+    stats: CommitStats,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Some(webhook_url) = config.notify_webhook_url.clone() {
+            if let Err(error) = send_webhook_notification(
+                &http_client,
+                &webhook_url,
+                &repository_name,
+                &commit_sha,
+                &commit_message,
+                &author_name,
+                &author_email,
+                stats,
+            )
+            .await
+            {
+                eprintln!("Failed to send webhook commit notification: {error:#}");
+            }
+        }
+
+        if let Some(email) = config.notify_email.clone() {
+            if let Err(error) =
+                send_email_notification(&config, &email, &commit_sha, &commit_message, stats)
+                    .await
+            {
+                eprintln!("Failed to send email commit notification: {error:#}");
+            }
+        }
+    })
+}
+
+async fn send_webhook_notification(
+    http_client: &Client,
+    webhook_url: &str,
+    repository_name: &str,
+    commit_sha: &str,
+    commit_message: &CommitMessage,
+    author_name: &str,
+    author_email: &str,
+    stats: CommitStats,
+) -> Result<()> {
+    let payload = WebhookPayload {
+        repository: repository_name.to_string(),
+        commits: vec![WebhookCommit {
+            id: commit_sha.to_string(),
+            message: commit_message.to_string(),
+            author: WebhookAuthor {
+                name: author_name.to_string(),
+                email: author_email.to_string(),
+            },
+            insertions: stats.insertions,
+            deletions: stats.deletions,
+        }],
+    };
+
+    http_client
+        .post(webhook_url)
+        .json(&payload)
+        .send()
+        .await
+        .context("Failed to send the webhook request")?
+        .error_for_status()
+        .context("The webhook endpoint returned an error status")?;
+
+    Ok(())
+}
+
+/// Builds and sends the notification email.
+///
+/// `SmtpTransport::send` is a blocking call, so it's offloaded to `spawn_blocking` — this
+/// function runs inside `notify_commit`'s task, which `git_commit` awaits, and a blocking send
+/// there would otherwise stall the tokio worker thread for as long as the relay takes to respond.
+async fn send_email_notification(
+    config: &Config,
+    to_email: &str,
+    commit_sha: &str,
+    commit_message: &CommitMessage,
+    stats: CommitStats,
+) -> Result<()> {
+    let body = format!(
+        "{}\n\ncommit {commit_sha}\n{} file(s) changed, {} insertion(s), {} deletion(s)",
+        commit_message, stats.files_changed, stats.insertions, stats.deletions
+    );
+
+    let email = Message::builder()
+        .from(config.notify_smtp_from.parse().context("Invalid notify_smtp_from address")?)
+        .to(to_email.parse().context("Invalid notify_email address")?)
+        .subject(format!("New commit: {}", commit_message.subject))
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .context("Failed to build the notification email")?;
+
+    let credentials = Credentials::new(
+        config.notify_smtp_username.clone(),
+        config.notify_smtp_password.clone(),
+    );
+    let smtp_host = config.notify_smtp_host.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mailer = SmtpTransport::relay(&smtp_host)
+            .context("Failed to configure the SMTP relay")?
+            .credentials(credentials)
+            .build();
+
+        mailer
+            .send(&email)
+            .context("Failed to send the notification email")?;
+
+        Ok::<(), anyhow::Error>(())
+    })
+    .await
+    .context("The SMTP send task panicked")??;
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use reqwest::Response;
+use serde::Deserialize;
+use std::io::Write;
+
+/// A single streamed chat-completion chunk, as sent over Server-Sent Events.
+#[derive(Deserialize)]
+struct StreamChunk {
+    choices: Vec<StreamChoice>,
+}
+
+#[derive(Deserialize)]
+struct StreamChoice {
+    index: usize,
+    delta: StreamDelta,
+}
+
+#[derive(Deserialize, Default)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
+/// Consumes a streamed chat-completion `response` (requested with `stream(true)`), returning
+/// every choice's fully-assembled message, ordered by its `index`, once the stream ends.
+///
+/// The API provider is expected to send newline-delimited `data: <json>` chunks terminated by a
+/// `data: [DONE]` sentinel, matching the OpenAI-compatible streaming format. When the request was
+/// made with `n > 1`, each chunk's `choices` carry the candidate `index` they belong to, so
+/// multiple candidates can be streamed and reassembled concurrently.
+///
+/// When `echo` is `true`, the first choice's tokens are printed to stderr as they arrive. Callers
+/// running several of these streams concurrently (e.g. one per candidate style) should pass
+/// `false` for all but one, otherwise their outputs interleave into unreadable stderr noise.
+pub async fn consume_chat_completion_stream(response: Response, echo: bool) -> Result<Vec<String>> {
+    let mut byte_stream = response.bytes_stream();
+    let mut buffer = String::new();
+    let mut messages: Vec<String> = Vec::new();
+
+    while let Some(chunk) = byte_stream.next().await {
+        let chunk = chunk.context("Failed to read a chunk of the streamed response")?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(newline_index) = buffer.find('\n') {
+            let line = buffer[..newline_index].trim().to_string();
+            buffer.drain(..=newline_index);
+
+            let Some(data) = line.strip_prefix("data: ") else {
+                continue;
+            };
+
+            if data == "[DONE]" {
+                continue;
+            }
+
+            let stream_chunk: StreamChunk = serde_json::from_str(data)
+                .context("Failed to parse a streamed response chunk")?;
+
+            for choice in &stream_chunk.choices {
+                let Some(content) = choice.delta.content.as_ref() else {
+                    continue;
+                };
+
+                if messages.len() <= choice.index {
+                    messages.resize(choice.index + 1, String::new());
+                }
+
+                if echo && choice.index == 0 {
+                    eprint!("{content}");
+                    std::io::stderr().flush().ok();
+                }
+
+                messages[choice.index].push_str(content);
+            }
+        }
+    }
+
+    Ok(messages)
+}